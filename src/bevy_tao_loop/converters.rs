@@ -9,17 +9,48 @@ use bevy::math::Vec2;
 use bevy::window::{CursorIcon, WindowLevel, WindowTheme};
 
 use tao::window::Window;
-use tao::{event::KeyEvent, keyboard::Key};
+use tao::{
+    event::KeyEvent,
+    keyboard::{Key, KeyCode as TaoKeyCode, KeyLocation},
+};
 
 pub fn convert_keyboard_input(keyboard_input: &KeyEvent, window: Entity) -> KeyboardInput {
     KeyboardInput {
-        scan_code: keyboard_input.physical_key.to_scancode().unwrap(),
+        scan_code: convert_scan_code(keyboard_input.physical_key),
         state: convert_element_state(keyboard_input.state),
-        key_code: convert_virtual_key_code(keyboard_input.logical_key.clone()),
+        key_code: convert_key_code(
+            keyboard_input.physical_key,
+            keyboard_input.key_without_modifiers(),
+            keyboard_input.location,
+        ),
         window,
     }
 }
 
+/// The layout-independent scan code of a key, use it for bindings that should follow the key
+/// position.
+pub fn convert_scan_code(physical_key: TaoKeyCode) -> u32 {
+    physical_key.to_scancode().unwrap_or(0)
+}
+
+/// The [`KeyCode`] of a key, given its logical key ignoring modifiers.
+pub fn convert_key_code(
+    physical_key: TaoKeyCode,
+    key_without_modifiers: Key,
+    location: KeyLocation,
+) -> Option<KeyCode> {
+    let physical_key_code = convert_physical_key_code(physical_key);
+    // The logical key can't tell left and right modifiers, or the numpad, apart. Otherwise
+    // follow the layout, ignoring modifiers so that shift+1 is still `Key1`, and fall back
+    // to the key position for characters with no `KeyCode`, such as on non-latin layouts.
+    match location {
+        KeyLocation::Standard => {
+            convert_virtual_key_code(key_without_modifiers).or(physical_key_code)
+        }
+        _ => physical_key_code.or_else(|| convert_virtual_key_code(key_without_modifiers)),
+    }
+}
+
 pub fn convert_element_state(element_state: tao::event::ElementState) -> ButtonState {
     match element_state {
         tao::event::ElementState::Pressed => ButtonState::Pressed,
@@ -81,32 +112,32 @@ pub fn convert_virtual_key_code(virtual_key_code: Key) -> Option<KeyCode> {
         Key::Character("8") => KeyCode::Key8,
         Key::Character("9") => KeyCode::Key9,
         Key::Character("0") => KeyCode::Key0,
-        Key::Character("A") => KeyCode::A,
-        Key::Character("B") => KeyCode::B,
-        Key::Character("C") => KeyCode::C,
-        Key::Character("D") => KeyCode::D,
-        Key::Character("E") => KeyCode::E,
-        Key::Character("F") => KeyCode::F,
-        Key::Character("G") => KeyCode::G,
-        Key::Character("H") => KeyCode::H,
-        Key::Character("I") => KeyCode::I,
-        Key::Character("J") => KeyCode::J,
-        Key::Character("K") => KeyCode::K,
-        Key::Character("L") => KeyCode::L,
-        Key::Character("M") => KeyCode::M,
-        Key::Character("N") => KeyCode::N,
-        Key::Character("O") => KeyCode::O,
-        Key::Character("P") => KeyCode::P,
-        Key::Character("Q") => KeyCode::Q,
-        Key::Character("R") => KeyCode::R,
-        Key::Character("S") => KeyCode::S,
-        Key::Character("T") => KeyCode::T,
-        Key::Character("U") => KeyCode::U,
-        Key::Character("V") => KeyCode::V,
-        Key::Character("W") => KeyCode::W,
-        Key::Character("X") => KeyCode::X,
-        Key::Character("Y") => KeyCode::Y,
-        Key::Character("Z") => KeyCode::Z,
+        Key::Character("A" | "a") => KeyCode::A,
+        Key::Character("B" | "b") => KeyCode::B,
+        Key::Character("C" | "c") => KeyCode::C,
+        Key::Character("D" | "d") => KeyCode::D,
+        Key::Character("E" | "e") => KeyCode::E,
+        Key::Character("F" | "f") => KeyCode::F,
+        Key::Character("G" | "g") => KeyCode::G,
+        Key::Character("H" | "h") => KeyCode::H,
+        Key::Character("I" | "i") => KeyCode::I,
+        Key::Character("J" | "j") => KeyCode::J,
+        Key::Character("K" | "k") => KeyCode::K,
+        Key::Character("L" | "l") => KeyCode::L,
+        Key::Character("M" | "m") => KeyCode::M,
+        Key::Character("N" | "n") => KeyCode::N,
+        Key::Character("O" | "o") => KeyCode::O,
+        Key::Character("P" | "p") => KeyCode::P,
+        Key::Character("Q" | "q") => KeyCode::Q,
+        Key::Character("R" | "r") => KeyCode::R,
+        Key::Character("S" | "s") => KeyCode::S,
+        Key::Character("T" | "t") => KeyCode::T,
+        Key::Character("U" | "u") => KeyCode::U,
+        Key::Character("V" | "v") => KeyCode::V,
+        Key::Character("W" | "w") => KeyCode::W,
+        Key::Character("X" | "x") => KeyCode::X,
+        Key::Character("Y" | "y") => KeyCode::Y,
+        Key::Character("Z" | "z") => KeyCode::Z,
         Key::Character("+") => KeyCode::Plus,
         Key::Character("*") => KeyCode::Asterisk,
         Key::Character("^") => KeyCode::Caret,
@@ -196,6 +227,160 @@ pub fn convert_virtual_key_code(virtual_key_code: Key) -> Option<KeyCode> {
     Some(key)
 }
 
+/// The [`KeyCode`] of the key at this position on a US keyboard.
+pub fn convert_physical_key_code(physical_key: TaoKeyCode) -> Option<KeyCode> {
+    let key = match physical_key {
+        TaoKeyCode::Digit1 => KeyCode::Key1,
+        TaoKeyCode::Digit2 => KeyCode::Key2,
+        TaoKeyCode::Digit3 => KeyCode::Key3,
+        TaoKeyCode::Digit4 => KeyCode::Key4,
+        TaoKeyCode::Digit5 => KeyCode::Key5,
+        TaoKeyCode::Digit6 => KeyCode::Key6,
+        TaoKeyCode::Digit7 => KeyCode::Key7,
+        TaoKeyCode::Digit8 => KeyCode::Key8,
+        TaoKeyCode::Digit9 => KeyCode::Key9,
+        TaoKeyCode::Digit0 => KeyCode::Key0,
+        TaoKeyCode::KeyA => KeyCode::A,
+        TaoKeyCode::KeyB => KeyCode::B,
+        TaoKeyCode::KeyC => KeyCode::C,
+        TaoKeyCode::KeyD => KeyCode::D,
+        TaoKeyCode::KeyE => KeyCode::E,
+        TaoKeyCode::KeyF => KeyCode::F,
+        TaoKeyCode::KeyG => KeyCode::G,
+        TaoKeyCode::KeyH => KeyCode::H,
+        TaoKeyCode::KeyI => KeyCode::I,
+        TaoKeyCode::KeyJ => KeyCode::J,
+        TaoKeyCode::KeyK => KeyCode::K,
+        TaoKeyCode::KeyL => KeyCode::L,
+        TaoKeyCode::KeyM => KeyCode::M,
+        TaoKeyCode::KeyN => KeyCode::N,
+        TaoKeyCode::KeyO => KeyCode::O,
+        TaoKeyCode::KeyP => KeyCode::P,
+        TaoKeyCode::KeyQ => KeyCode::Q,
+        TaoKeyCode::KeyR => KeyCode::R,
+        TaoKeyCode::KeyS => KeyCode::S,
+        TaoKeyCode::KeyT => KeyCode::T,
+        TaoKeyCode::KeyU => KeyCode::U,
+        TaoKeyCode::KeyV => KeyCode::V,
+        TaoKeyCode::KeyW => KeyCode::W,
+        TaoKeyCode::KeyX => KeyCode::X,
+        TaoKeyCode::KeyY => KeyCode::Y,
+        TaoKeyCode::KeyZ => KeyCode::Z,
+        TaoKeyCode::Backquote => KeyCode::Grave,
+        TaoKeyCode::Backslash => KeyCode::Backslash,
+        TaoKeyCode::BracketLeft => KeyCode::BracketLeft,
+        TaoKeyCode::BracketRight => KeyCode::BracketRight,
+        TaoKeyCode::Comma => KeyCode::Comma,
+        TaoKeyCode::Equal => KeyCode::Equals,
+        TaoKeyCode::IntlBackslash => KeyCode::Oem102,
+        TaoKeyCode::IntlYen => KeyCode::Yen,
+        TaoKeyCode::Minus => KeyCode::Minus,
+        TaoKeyCode::Plus => KeyCode::Plus,
+        TaoKeyCode::Period => KeyCode::Period,
+        TaoKeyCode::Quote => KeyCode::Apostrophe,
+        TaoKeyCode::Semicolon => KeyCode::Semicolon,
+        TaoKeyCode::Slash => KeyCode::Slash,
+        TaoKeyCode::AltLeft => KeyCode::AltLeft,
+        TaoKeyCode::AltRight => KeyCode::AltRight,
+        TaoKeyCode::ControlLeft => KeyCode::ControlLeft,
+        TaoKeyCode::ControlRight => KeyCode::ControlRight,
+        TaoKeyCode::ShiftLeft => KeyCode::ShiftLeft,
+        TaoKeyCode::ShiftRight => KeyCode::ShiftRight,
+        TaoKeyCode::SuperLeft => KeyCode::SuperLeft,
+        TaoKeyCode::SuperRight => KeyCode::SuperRight,
+        TaoKeyCode::Backspace => KeyCode::Back,
+        TaoKeyCode::CapsLock => KeyCode::Capital,
+        TaoKeyCode::ContextMenu => KeyCode::Apps,
+        TaoKeyCode::Enter => KeyCode::Return,
+        TaoKeyCode::Space => KeyCode::Space,
+        TaoKeyCode::Tab => KeyCode::Tab,
+        TaoKeyCode::Convert => KeyCode::Convert,
+        TaoKeyCode::NonConvert => KeyCode::NoConvert,
+        TaoKeyCode::KanaMode => KeyCode::Kana,
+        TaoKeyCode::Delete => KeyCode::Delete,
+        TaoKeyCode::End => KeyCode::End,
+        TaoKeyCode::Home => KeyCode::Home,
+        TaoKeyCode::Insert => KeyCode::Insert,
+        TaoKeyCode::PageDown => KeyCode::PageDown,
+        TaoKeyCode::PageUp => KeyCode::PageUp,
+        TaoKeyCode::ArrowDown => KeyCode::Down,
+        TaoKeyCode::ArrowLeft => KeyCode::Left,
+        TaoKeyCode::ArrowRight => KeyCode::Right,
+        TaoKeyCode::ArrowUp => KeyCode::Up,
+        TaoKeyCode::NumLock => KeyCode::Numlock,
+        TaoKeyCode::Numpad0 => KeyCode::Numpad0,
+        TaoKeyCode::Numpad1 => KeyCode::Numpad1,
+        TaoKeyCode::Numpad2 => KeyCode::Numpad2,
+        TaoKeyCode::Numpad3 => KeyCode::Numpad3,
+        TaoKeyCode::Numpad4 => KeyCode::Numpad4,
+        TaoKeyCode::Numpad5 => KeyCode::Numpad5,
+        TaoKeyCode::Numpad6 => KeyCode::Numpad6,
+        TaoKeyCode::Numpad7 => KeyCode::Numpad7,
+        TaoKeyCode::Numpad8 => KeyCode::Numpad8,
+        TaoKeyCode::Numpad9 => KeyCode::Numpad9,
+        TaoKeyCode::NumpadAdd => KeyCode::NumpadAdd,
+        TaoKeyCode::NumpadComma => KeyCode::NumpadComma,
+        TaoKeyCode::NumpadDecimal => KeyCode::NumpadDecimal,
+        TaoKeyCode::NumpadDivide => KeyCode::NumpadDivide,
+        TaoKeyCode::NumpadEnter => KeyCode::NumpadEnter,
+        TaoKeyCode::NumpadEqual => KeyCode::NumpadEquals,
+        TaoKeyCode::NumpadMultiply => KeyCode::NumpadMultiply,
+        TaoKeyCode::NumpadSubtract => KeyCode::NumpadSubtract,
+        TaoKeyCode::Escape => KeyCode::Escape,
+        TaoKeyCode::PrintScreen => KeyCode::Snapshot,
+        TaoKeyCode::ScrollLock => KeyCode::Scroll,
+        TaoKeyCode::Pause => KeyCode::Pause,
+        TaoKeyCode::BrowserBack => KeyCode::WebBack,
+        TaoKeyCode::BrowserFavorites => KeyCode::WebFavorites,
+        TaoKeyCode::BrowserForward => KeyCode::WebForward,
+        TaoKeyCode::BrowserHome => KeyCode::WebHome,
+        TaoKeyCode::BrowserRefresh => KeyCode::WebRefresh,
+        TaoKeyCode::BrowserSearch => KeyCode::WebSearch,
+        TaoKeyCode::BrowserStop => KeyCode::WebStop,
+        TaoKeyCode::LaunchMail => KeyCode::Mail,
+        TaoKeyCode::MediaPlayPause => KeyCode::PlayPause,
+        TaoKeyCode::MediaSelect => KeyCode::MediaSelect,
+        TaoKeyCode::MediaStop => KeyCode::MediaStop,
+        TaoKeyCode::MediaTrackNext => KeyCode::NextTrack,
+        TaoKeyCode::MediaTrackPrevious => KeyCode::PrevTrack,
+        TaoKeyCode::Power => KeyCode::Power,
+        TaoKeyCode::Sleep => KeyCode::Sleep,
+        TaoKeyCode::AudioVolumeDown => KeyCode::VolumeDown,
+        TaoKeyCode::AudioVolumeMute => KeyCode::Mute,
+        TaoKeyCode::AudioVolumeUp => KeyCode::VolumeUp,
+        TaoKeyCode::WakeUp => KeyCode::Wake,
+        TaoKeyCode::Copy => KeyCode::Copy,
+        TaoKeyCode::Cut => KeyCode::Cut,
+        TaoKeyCode::Paste => KeyCode::Paste,
+        TaoKeyCode::F1 => KeyCode::F1,
+        TaoKeyCode::F2 => KeyCode::F2,
+        TaoKeyCode::F3 => KeyCode::F3,
+        TaoKeyCode::F4 => KeyCode::F4,
+        TaoKeyCode::F5 => KeyCode::F5,
+        TaoKeyCode::F6 => KeyCode::F6,
+        TaoKeyCode::F7 => KeyCode::F7,
+        TaoKeyCode::F8 => KeyCode::F8,
+        TaoKeyCode::F9 => KeyCode::F9,
+        TaoKeyCode::F10 => KeyCode::F10,
+        TaoKeyCode::F11 => KeyCode::F11,
+        TaoKeyCode::F12 => KeyCode::F12,
+        TaoKeyCode::F13 => KeyCode::F13,
+        TaoKeyCode::F14 => KeyCode::F14,
+        TaoKeyCode::F15 => KeyCode::F15,
+        TaoKeyCode::F16 => KeyCode::F16,
+        TaoKeyCode::F17 => KeyCode::F17,
+        TaoKeyCode::F18 => KeyCode::F18,
+        TaoKeyCode::F19 => KeyCode::F19,
+        TaoKeyCode::F20 => KeyCode::F20,
+        TaoKeyCode::F21 => KeyCode::F21,
+        TaoKeyCode::F22 => KeyCode::F22,
+        TaoKeyCode::F23 => KeyCode::F23,
+        TaoKeyCode::F24 => KeyCode::F24,
+        _ => return None,
+    };
+    Some(key)
+}
+
 pub fn convert_cursor_icon(cursor_icon: CursorIcon) -> tao::window::CursorIcon {
     match cursor_icon {
         CursorIcon::Default => tao::window::CursorIcon::Default,
@@ -260,3 +445,41 @@ pub fn convert_window_theme(theme: WindowTheme) -> tao::window::Theme {
         WindowTheme::Dark => tao::window::Theme::Dark,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn azerty_follows_layout_and_keeps_scan_code() {
+        // The key at the W position of a QWERTY keyboard types a "z" on AZERTY.
+        let key_code =
+            convert_key_code(TaoKeyCode::KeyW, Key::Character("z"), KeyLocation::Standard);
+        assert_eq!(key_code, Some(KeyCode::Z));
+
+        let scan_code = convert_scan_code(TaoKeyCode::KeyW);
+        assert_eq!(Some(scan_code), TaoKeyCode::KeyW.to_scancode());
+        assert_ne!(scan_code, convert_scan_code(TaoKeyCode::KeyZ));
+    }
+
+    #[test]
+    fn non_latin_character_falls_back_to_physical_key() {
+        // The key at the W position of a QWERTY keyboard types a "ц" on a Russian layout.
+        let key_code =
+            convert_key_code(TaoKeyCode::KeyW, Key::Character("ц"), KeyLocation::Standard);
+        assert_eq!(key_code, Some(KeyCode::W));
+    }
+
+    #[test]
+    fn modifiers_keep_their_side() {
+        let key_code = convert_key_code(TaoKeyCode::ShiftRight, Key::Shift, KeyLocation::Right);
+        assert_eq!(key_code, Some(KeyCode::ShiftRight));
+    }
+
+    #[test]
+    fn lowercase_letters_map_to_key_code() {
+        let key_code =
+            convert_key_code(TaoKeyCode::KeyA, Key::Character("a"), KeyLocation::Standard);
+        assert_eq!(key_code, Some(KeyCode::A));
+    }
+}
@@ -10,37 +10,46 @@
 // pub mod accessibility;
 mod converters;
 mod system;
+mod tao_clipboard;
 mod tao_config;
+mod tao_events;
 mod tao_windows;
 
 use bevy::ecs::system::{SystemParam, SystemState};
 use bevy::tasks::tick_global_task_pools_on_main_thread;
-use system::{changed_window, create_window, despawn_window, CachedWindow};
+use system::{
+    changed_window, control_window, create_window, despawn_window, recreate_window,
+    update_relative_mouse_mode, update_window_icon, CachedWindow,
+};
 
+pub use tao_clipboard::*;
 pub use tao_config::*;
+pub use tao_events::*;
 pub use tao_windows::*;
 
-use bevy::app::{App, AppExit, Last, Plugin};
+use bevy::app::{App, AppExit, Last, Plugin, PostUpdate};
 use bevy::ecs::event::{Events, ManualEventReader};
 use bevy::ecs::prelude::*;
 use bevy::input::{
     keyboard::KeyboardInput,
-    mouse::{MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
+    mouse::{MouseButton, MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel},
     touch::TouchInput,
+    ButtonState,
 };
 use bevy::log::{error, info, trace, warn};
 use bevy::math::{ivec2, DVec2, Vec2};
-use bevy::utils::Instant;
+use bevy::utils::{HashSet, Instant};
 use bevy::window::{
     exit_on_all_closed, CursorEntered, CursorLeft, CursorMoved, FileDragAndDrop, ReceivedCharacter,
     RequestRedraw, Window, WindowBackendScaleFactorChanged, WindowCloseRequested, WindowCreated,
-    WindowDestroyed, WindowFocused, WindowMoved, WindowResized, WindowScaleFactorChanged,
-    WindowThemeChanged,
+    WindowDestroyed, WindowFocused, WindowMode, WindowMoved, WindowResizeConstraints,
+    WindowResized, WindowScaleFactorChanged, WindowThemeChanged,
 };
 
 use tao::{
+    dpi::PhysicalSize,
     event::{self, DeviceEvent, Event, StartCause, WindowEvent},
-    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    event_loop::{ControlFlow, DeviceEventFilter, EventLoop, EventLoopWindowTarget},
 };
 
 // use crate::accessibility::{
@@ -55,12 +64,33 @@ pub struct TaoPlugin;
 
 impl Plugin for TaoPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<TaoSettings>();
+        select_linux_window_backend(app.world.resource::<TaoSettings>().linux_window_backend);
+
         let event_loop = EventLoop::new();
+        log_display_server(&event_loop);
         app.insert_non_send_resource(event_loop);
 
         app.init_non_send_resource::<TaoWindows>()
-            .init_resource::<TaoSettings>()
+            .init_non_send_resource::<TaoClipboard>()
+            .init_resource::<RelativeMouseMode>()
+            .init_resource::<ScrollLineHeight>()
+            .init_resource::<SystemTheme>()
+            .add_event::<DoubleClick>()
+            .add_event::<PixelMouseWheel>()
+            .add_event::<RecreateWindow>()
+            .add_event::<WindowRecreated>()
+            .add_event::<DragWindow>()
+            .add_event::<ToggleMaximizeWindow>()
+            .add_event::<SystemThemeChanged>()
+            .add_event::<Timestamped<KeyboardInput>>()
+            .add_event::<Timestamped<MouseButtonInput>>()
+            .add_event::<Timestamped<MouseWheel>>()
+            .add_event::<Timestamped<MouseMotion>>()
+            .add_event::<Timestamped<CursorMoved>>()
+            .add_event::<Timestamped<TouchInput>>()
             .set_runner(tao_runner)
+            .add_systems(PostUpdate, recreate_window)
             // exit_on_all_closed only uses the query to determine if the query is empty,
             // and so doesn't care about ordering relative to changed_window
             .add_systems(
@@ -69,6 +99,9 @@ impl Plugin for TaoPlugin {
                     changed_window.ambiguous_with(exit_on_all_closed),
                     // Update the state of the window before attempting to despawn to ensure consistent event ordering
                     despawn_window.after(changed_window),
+                    update_window_icon,
+                    update_relative_mouse_mode.before(changed_window),
+                    control_window,
                 ),
             );
 
@@ -78,13 +111,14 @@ impl Plugin for TaoPlugin {
             Query<(Entity, &mut Window)>,
             EventWriter<WindowCreated>,
             NonSendMut<TaoWindows>,
+            ResMut<SystemTheme>,
         )> = SystemState::from_world(&mut app.world);
 
         // And for ios and macos, we should not create window early, all ui related code should be executed inside
         // UIApplicationMain/NSApplicationMain.
         #[cfg(not(any(target_os = "android", target_os = "ios", target_os = "macos")))]
         {
-            let (commands, event_loop, mut new_windows, event_writer, tao_windows) =
+            let (commands, event_loop, mut new_windows, event_writer, tao_windows, system_theme) =
                 create_window_system_state.get_mut(&mut app.world);
 
             // Here we need to create a tao window and give it a WindowHandle which the renderer can use.
@@ -96,6 +130,7 @@ impl Plugin for TaoPlugin {
                 new_windows.iter_mut(),
                 event_writer,
                 tao_windows,
+                system_theme,
             );
         }
 
@@ -103,6 +138,144 @@ impl Plugin for TaoPlugin {
     }
 }
 
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn select_linux_window_backend(backend: LinuxWindowBackend) {
+    // GTK reads this when tao initializes it in `EventLoop::new`, so it must be set before.
+    let gdk_backend = match backend {
+        LinuxWindowBackend::Auto => return,
+        LinuxWindowBackend::X11 => "x11",
+        LinuxWindowBackend::Wayland => "wayland",
+    };
+    match std::env::var("GDK_BACKEND") {
+        Ok(previous) if previous != gdk_backend => warn!(
+            target: "bevy_tao",
+            "Overriding GDK_BACKEND={previous} with {gdk_backend} from TaoSettings::linux_window_backend"
+        ),
+        _ => {}
+    }
+    // Setting the environment isn't thread-safe: with glibc, a `getenv` running concurrently in
+    // another thread is undefined behavior, and the task pool threads already exist at this point.
+    // This is only sound as long as no other thread reads the environment while plugins are built.
+    std::env::set_var("GDK_BACKEND", gdk_backend);
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn select_linux_window_backend(_backend: LinuxWindowBackend) {}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn log_display_server(event_loop: &EventLoop<()>) {
+    use tao::platform::unix::EventLoopWindowTargetExtUnix;
+
+    // Unlike the raw display handle, this doesn't open a new X11 connection.
+    if event_loop.is_wayland() {
+        info!(target: "bevy_tao", "tao is using the Wayland display server");
+    } else {
+        info!(target: "bevy_tao", "tao is using the X11 display server");
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn log_display_server(_event_loop: &EventLoop<()>) {}
+
+/// The physical position of the cursor on the screen, if the window position of the cursor
+/// can't be trusted once a drag leaves the window.
+///
+/// GTK reports the cursor position relative to the window under it, which isn't ours anymore
+/// once the drag leaves it. On Wayland, the screen position isn't available either, and
+/// positions outside the window stay as tao reports them.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn screen_cursor_position(
+    tao_window: &tao::window::Window,
+    event_loop: &EventLoopWindowTarget<()>,
+) -> Option<DVec2> {
+    use tao::platform::unix::EventLoopWindowTargetExtUnix;
+
+    if event_loop.is_wayland() {
+        return None;
+    }
+    let position = tao_window.cursor_position().ok()?;
+    Some(DVec2::new(position.x, position.y))
+}
+
+// Other platforms capture the pointer themselves, and report positions relative to the window.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn screen_cursor_position(
+    _tao_window: &tao::window::Window,
+    _event_loop: &EventLoopWindowTarget<()>,
+) -> Option<DVec2> {
+    None
+}
+
+/// The size closest to `size` with the given aspect ratio, following whichever dimension changed
+/// the most since `previous`, and within the window's resize `constraints`.
+fn lock_aspect_ratio(
+    size: PhysicalSize<u32>,
+    previous: PhysicalSize<u32>,
+    aspect_ratio: f32,
+    constraints: &WindowResizeConstraints,
+    scale_factor: f64,
+) -> PhysicalSize<u32> {
+    if !(aspect_ratio.is_finite() && aspect_ratio > 0.0) {
+        return size;
+    }
+    let constraints = constraints.check_constraints();
+    let scale_factor = scale_factor as f32;
+    // Narrow the width range so that the matching height is also within its own range.
+    let min_width = (constraints.min_width * scale_factor)
+        .max(constraints.min_height * scale_factor * aspect_ratio);
+    let max_width = (constraints.max_width * scale_factor)
+        .min(constraints.max_height * scale_factor * aspect_ratio);
+
+    let width_driven = size.width.abs_diff(previous.width) >= size.height.abs_diff(previous.height);
+    let mut width = if width_driven {
+        size.width as f32
+    } else {
+        size.height as f32 * aspect_ratio
+    };
+    if min_width <= max_width {
+        width = width.clamp(min_width, max_width);
+    }
+    let width = width.round().max(1.0);
+    let height = (width / aspect_ratio).round().max(1.0);
+    PhysicalSize::new(width as u32, height as u32)
+}
+
 fn run<F>(event_loop: EventLoop<()>, event_handler: F) -> !
 where
     F: 'static + FnMut(Event<'_, ()>, &EventLoopWindowTarget<()>, &mut ControlFlow),
@@ -155,6 +328,7 @@ struct WindowEvents<'w> {
     window_focused: EventWriter<'w, WindowFocused>,
     window_moved: EventWriter<'w, WindowMoved>,
     window_theme_changed: EventWriter<'w, WindowThemeChanged>,
+    system_theme_changed: EventWriter<'w, SystemThemeChanged>,
     window_destroyed: EventWriter<'w, WindowDestroyed>,
 }
 
@@ -163,10 +337,35 @@ struct InputEvents<'w> {
     keyboard_input: EventWriter<'w, KeyboardInput>,
     character_input: EventWriter<'w, ReceivedCharacter>,
     mouse_button_input: EventWriter<'w, MouseButtonInput>,
+    double_click: EventWriter<'w, DoubleClick>,
     mouse_wheel_input: EventWriter<'w, MouseWheel>,
+    pixel_mouse_wheel_input: EventWriter<'w, PixelMouseWheel>,
     touch_input: EventWriter<'w, TouchInput>,
 }
 
+#[derive(SystemParam)]
+struct TimestampedInputEvents<'w> {
+    keyboard_input: EventWriter<'w, Timestamped<KeyboardInput>>,
+    mouse_button_input: EventWriter<'w, Timestamped<MouseButtonInput>>,
+    mouse_wheel_input: EventWriter<'w, Timestamped<MouseWheel>>,
+    cursor_moved: EventWriter<'w, Timestamped<CursorMoved>>,
+    touch_input: EventWriter<'w, Timestamped<TouchInput>>,
+}
+
+/// Send a [`Timestamped`] copy of `event` if `received` is set.
+fn send_timestamped<T: bevy::ecs::event::Event + Clone>(
+    writer: &mut EventWriter<Timestamped<T>>,
+    received: Option<Instant>,
+    event: &T,
+) {
+    if let Some(received) = received {
+        writer.send(Timestamped {
+            event: event.clone(),
+            received,
+        });
+    }
+}
+
 #[derive(SystemParam)]
 struct CursorEvents<'w> {
     cursor_moved: EventWriter<'w, CursorMoved>,
@@ -197,20 +396,77 @@ struct TaoPersistentState {
     /// Tracks if the event loop was started this frame because of a [`ControlFlow::WaitUntil`]
     /// timeout.
     timeout_reached: bool,
+    /// Tracks whether tao asked for a window to be redrawn, for example after it was uncovered.
+    os_redraw_requested: bool,
     last_update: Instant,
+    /// The last mouse button press, used to detect double clicks.
+    last_click: Option<LastClick>,
+    /// The window that keeps receiving the cursor while mouse buttons are held.
+    pointer_capture: Option<PointerCapture>,
+}
+
+/// A mouse button press that may be followed by a second one to form a [`DoubleClick`].
+struct LastClick {
+    window: Entity,
+    button: MouseButton,
+    position: Vec2,
+    time: Instant,
+}
+
+/// A drag that started in `window`, which keeps the cursor until all buttons are released.
+///
+/// Bevy isn't told that the cursor left the window while it is captured, so drags keep getting
+/// [`CursorMoved`] events, with positions that may be outside the window bounds.
+struct PointerCapture {
+    window: Entity,
+    buttons: HashSet<MouseButton>,
+    /// Whether tao reported the cursor leaving the window during the drag.
+    cursor_left: bool,
+    /// Offset between the screen and window cursor positions, where the platform needs it to
+    /// place the cursor once it is outside the window. See [`screen_cursor_position`].
+    screen_offset: Option<DVec2>,
 }
 impl Default for TaoPersistentState {
     fn default() -> Self {
         Self {
-            active: false,
+            // tao only sends `Resumed` on Android and iOS, desktop apps start active.
+            active: cfg!(not(any(target_os = "android", target_os = "ios"))),
             low_power_event: false,
             redraw_request_sent: false,
             timeout_reached: false,
+            os_redraw_requested: false,
             last_update: Instant::now(),
+            last_click: None,
+            pointer_capture: None,
         }
     }
 }
 
+/// Tells bevy that the cursor left the window of a drag that ended, if it did during the drag.
+fn end_pointer_capture(
+    capture: PointerCapture,
+    window_query: &mut Query<(&mut Window, &mut CachedWindow)>,
+    cursor_left_events: &mut EventWriter<CursorLeft>,
+) {
+    if !capture.cursor_left {
+        return;
+    }
+    if let Ok((mut window, mut cache)) = window_query.get_mut(capture.window) {
+        window.set_physical_cursor_position(None);
+        cache.window = window.clone();
+    }
+    cursor_left_events.send(CursorLeft {
+        window: capture.window,
+    });
+}
+
+/// Whether an [`AppExit`] event was sent since the last call.
+fn app_exit_requested(app: &App, app_exit_event_reader: &mut ManualEventReader<AppExit>) -> bool {
+    app.world
+        .get_resource::<Events<AppExit>>()
+        .is_some_and(|app_exit_events| app_exit_event_reader.iter(app_exit_events).last().is_some())
+}
+
 /// The default [`App::runner`] for the [`TaoPlugin`] plugin.
 ///
 /// Overriding the app's [runner](bevy::app::App::runner) while using `TaoPlugin` will bypass the `EventLoop`.
@@ -227,7 +483,13 @@ pub fn tao_runner(mut app: App) {
     app.world
         .insert_non_send_resource(event_loop.create_proxy());
 
-    let return_from_run = app.world.resource::<TaoSettings>().return_from_run;
+    let settings = app.world.resource::<TaoSettings>();
+    let return_from_run = settings.return_from_run;
+    event_loop.set_device_event_filter(if settings.unfocused_device_events {
+        DeviceEventFilter::Never
+    } else {
+        DeviceEventFilter::Unfocused
+    });
 
     trace!("Entering tao event loop");
 
@@ -236,15 +498,21 @@ pub fn tao_runner(mut app: App) {
         Query<(Entity, &mut Window), Added<Window>>,
         EventWriter<WindowCreated>,
         NonSendMut<TaoWindows>,
+        ResMut<SystemTheme>,
     )> = SystemState::from_world(&mut app.world);
 
+    let mut focused_window_state: SystemState<(Res<TaoSettings>, Query<&Window>)> =
+        SystemState::from_world(&mut app.world);
+
     let mut finished_and_setup_done = false;
+    let mut exiting = false;
 
     let event_handler = move |event: Event<()>,
                               event_loop: &EventLoopWindowTarget<()>,
                               control_flow: &mut ControlFlow| {
         #[cfg(feature = "trace")]
-        let _span = bevy::utils::tracing::info_span!("tao event_handler").entered();
+        let _span =
+            bevy::utils::tracing::info_span!(target: "bevy_tao", "tao event_handler").entered();
 
         if !finished_and_setup_done {
             if !app.ready() {
@@ -257,11 +525,12 @@ pub fn tao_runner(mut app: App) {
             }
         }
 
-        if let Some(app_exit_events) = app.world.get_resource::<Events<AppExit>>() {
-            if app_exit_event_reader.iter(app_exit_events).last().is_some() {
-                *control_flow = ControlFlow::Exit;
-                return;
-            }
+        // Once exiting, tao may still dispatch a few events before the loop returns. Ignore them
+        // so that the app isn't updated and no window is created after it asked to exit.
+        if exiting || app_exit_requested(&app, &mut app_exit_event_reader) {
+            exiting = true;
+            *control_flow = ControlFlow::Exit;
+            return;
         }
 
         match event {
@@ -271,9 +540,18 @@ pub fn tao_runner(mut app: App) {
                 // because we don't know if the criteria for an app update were met until the end of
                 // the frame.
                 let auto_timeout_reached = matches!(start, StartCause::ResumeTimeReached { .. });
+                let (settings, window_focused_query) = focused_window_state.get(&app.world);
+                let focused = window_focused_query.iter().any(|window| window.focused);
+                let now = Instant::now();
+                let manual_timeout_reached = match settings.update_mode(focused) {
+                    UpdateMode::Continuous => false,
+                    UpdateMode::Reactive { wait } | UpdateMode::ReactiveLowPower { wait } => {
+                        now.duration_since(tao_state.last_update) >= *wait
+                    }
+                };
                 // The low_power_event state and timeout must be reset at the start of every frame.
                 tao_state.low_power_event = false;
-                tao_state.timeout_reached = auto_timeout_reached;
+                tao_state.timeout_reached = auto_timeout_reached || manual_timeout_reached;
             }
             event::Event::WindowEvent {
                 event,
@@ -288,6 +566,12 @@ pub fn tao_runner(mut app: App) {
                     InputEvents,
                     CursorEvents,
                     EventWriter<FileDragAndDrop>,
+                    Res<TaoSettings>,
+                    TimestampedInputEvents,
+                    Res<RelativeMouseMode>,
+                    Res<ScrollLineHeight>,
+                    Option<Res<AspectRatioLock>>,
+                    ResMut<SystemTheme>,
                 )> = SystemState::new(&mut app.world);
                 let (
                     tao_windows,
@@ -296,7 +580,14 @@ pub fn tao_runner(mut app: App) {
                     mut input_events,
                     mut cursor_events,
                     mut file_drag_and_drop_events,
+                    settings,
+                    mut timestamped_events,
+                    relative_mouse_mode,
+                    scroll_line_height,
+                    aspect_ratio_lock,
+                    mut system_theme,
                 ) = system_state.get_mut(&mut app.world);
+                let received = settings.timestamp_input.then(Instant::now);
 
                 // Entity of this window
                 let window_entity =
@@ -310,6 +601,37 @@ pub fn tao_runner(mut app: App) {
                         return;
                     };
 
+                // The button release ending a drag can be lost, for example when switching to
+                // another app mid-drag. Don't wait for it once the window loses focus, or when a
+                // press in another window starts a new drag. The release may also happen over
+                // another window.
+                let capture_ended =
+                    tao_state
+                        .pointer_capture
+                        .as_mut()
+                        .is_some_and(|capture| match event {
+                            WindowEvent::Focused(false) => capture.window == window_entity,
+                            WindowEvent::MouseInput {
+                                state: event::ElementState::Pressed,
+                                ..
+                            } => capture.window != window_entity,
+                            WindowEvent::MouseInput {
+                                state: event::ElementState::Released,
+                                button,
+                                ..
+                            } => {
+                                capture
+                                    .buttons
+                                    .remove(&converters::convert_mouse_button(button));
+                                capture.buttons.is_empty()
+                            }
+                            _ => false,
+                        });
+                if capture_ended {
+                    let capture = tao_state.pointer_capture.take().unwrap();
+                    end_pointer_capture(capture, &mut window_query, &mut cursor_events.cursor_left);
+                }
+
                 let (mut window, mut cache) =
                     if let Ok((window, info)) = window_query.get_mut(window_entity) {
                         (window, info)
@@ -321,10 +643,38 @@ pub fn tao_runner(mut app: App) {
                         return;
                     };
 
+                #[cfg(feature = "trace")]
+                let _span =
+                    bevy::utils::tracing::info_span!(target: "bevy_tao", "tao window event", ?window_entity).entered();
+                trace!(target: "bevy_tao", "Window {window_entity:?} received {event:?}");
+
                 tao_state.low_power_event = true;
 
                 match event {
                     WindowEvent::Resized(size) => {
+                        let tao_window = tao_windows.get_window(window_entity);
+                        if let (Some(aspect_ratio_lock), Some(tao_window)) =
+                            (&aspect_ratio_lock, tao_window)
+                        {
+                            if window.mode == WindowMode::Windowed && !tao_window.is_maximized() {
+                                let locked_size = lock_aspect_ratio(
+                                    size,
+                                    PhysicalSize::new(
+                                        window.physical_width(),
+                                        window.physical_height(),
+                                    ),
+                                    aspect_ratio_lock.0,
+                                    &window.resize_constraints,
+                                    window.scale_factor(),
+                                );
+                                // The window manager may not apply it, the `Resized` event
+                                // that follows the request reports the actual size.
+                                if locked_size != size {
+                                    tao_window.set_inner_size(locked_size);
+                                }
+                            }
+                        }
+
                         window
                             .resolution
                             .set_physical_resolution(size.width, size.height);
@@ -343,39 +693,159 @@ pub fn tao_runner(mut app: App) {
                             });
                     }
                     WindowEvent::KeyboardInput { ref event, .. } => {
-                        input_events
-                            .keyboard_input
-                            .send(converters::convert_keyboard_input(event, window_entity));
+                        let keyboard_input =
+                            converters::convert_keyboard_input(event, window_entity);
+                        send_timestamped(
+                            &mut timestamped_events.keyboard_input,
+                            received,
+                            &keyboard_input,
+                        );
+                        input_events.keyboard_input.send(keyboard_input);
                     }
+                    // Only raw mouse motion is forwarded in relative mouse mode.
+                    WindowEvent::CursorMoved { .. } if relative_mouse_mode.0 => {}
                     WindowEvent::CursorMoved { position, .. } => {
-                        let physical_position = DVec2::new(position.x, position.y);
+                        let mut physical_position = DVec2::new(position.x, position.y);
+
+                        if let Some(capture) = tao_state
+                            .pointer_capture
+                            .as_mut()
+                            .filter(|capture| capture.window == window_entity)
+                        {
+                            let screen_position =
+                                tao_windows
+                                    .get_window(window_entity)
+                                    .and_then(|tao_window| {
+                                        screen_cursor_position(tao_window, event_loop)
+                                    });
+                            if let Some(screen_position) = screen_position {
+                                if !capture.cursor_left {
+                                    capture.screen_offset =
+                                        Some(screen_position - physical_position);
+                                } else if let Some(offset) = capture.screen_offset {
+                                    physical_position = screen_position - offset;
+                                }
+                            }
+                        }
 
                         window.set_physical_cursor_position(Some(physical_position));
 
-                        cursor_events.cursor_moved.send(CursorMoved {
+                        let position =
+                            (physical_position / window.resolution.scale_factor()).as_vec2();
+                        // Moving away from the last click means the next one can't complete it.
+                        if let Some(last_click) = &tao_state.last_click {
+                            if last_click.position.distance(position)
+                                > settings.double_click_distance
+                            {
+                                tao_state.last_click = None;
+                            }
+                        }
+
+                        let cursor_moved = CursorMoved {
                             window: window_entity,
-                            position: (physical_position / window.resolution.scale_factor())
-                                .as_vec2(),
-                        });
+                            position,
+                        };
+                        send_timestamped(
+                            &mut timestamped_events.cursor_moved,
+                            received,
+                            &cursor_moved,
+                        );
+                        cursor_events.cursor_moved.send(cursor_moved);
                     }
                     WindowEvent::CursorEntered { .. } => {
-                        cursor_events.cursor_entered.send(CursorEntered {
-                            window: window_entity,
-                        });
+                        match &mut tao_state.pointer_capture {
+                            // Bevy was never told that the cursor left.
+                            Some(capture) if capture.window == window_entity => {
+                                capture.cursor_left = false;
+                            }
+                            _ => {
+                                cursor_events.cursor_entered.send(CursorEntered {
+                                    window: window_entity,
+                                });
+                            }
+                        }
                     }
-                    WindowEvent::CursorLeft { .. } => {
-                        window.set_physical_cursor_position(None);
+                    WindowEvent::CursorLeft { .. } => match &mut tao_state.pointer_capture {
+                        // Wait for the end of the drag to tell bevy.
+                        Some(capture) if capture.window == window_entity => {
+                            capture.cursor_left = true;
+                        }
+                        _ => {
+                            window.set_physical_cursor_position(None);
 
-                        cursor_events.cursor_left.send(CursorLeft {
-                            window: window_entity,
-                        });
-                    }
+                            cursor_events.cursor_left.send(CursorLeft {
+                                window: window_entity,
+                            });
+                        }
+                    },
                     WindowEvent::MouseInput { state, button, .. } => {
-                        input_events.mouse_button_input.send(MouseButtonInput {
-                            button: converters::convert_mouse_button(button),
-                            state: converters::convert_element_state(state),
+                        let button = converters::convert_mouse_button(button);
+                        let state = converters::convert_element_state(state);
+
+                        if state == ButtonState::Pressed {
+                            let capture = match &mut tao_state.pointer_capture {
+                                Some(capture) if capture.window == window_entity => capture,
+                                capture => {
+                                    let screen_offset = tao_windows
+                                        .get_window(window_entity)
+                                        .and_then(|tao_window| {
+                                            screen_cursor_position(tao_window, event_loop)
+                                        })
+                                        .zip(window.physical_cursor_position())
+                                        .map(|(screen, window)| screen - window.as_dvec2());
+                                    capture.insert(PointerCapture {
+                                        window: window_entity,
+                                        buttons: HashSet::new(),
+                                        cursor_left: false,
+                                        screen_offset,
+                                    })
+                                }
+                            };
+                            capture.buttons.insert(button);
+                        }
+
+                        if let (ButtonState::Pressed, Some(position)) =
+                            (state, window.cursor_position())
+                        {
+                            let now = Instant::now();
+                            let is_double_click =
+                                tao_state.last_click.as_ref().is_some_and(|last| {
+                                    last.window == window_entity
+                                        && last.button == button
+                                        && now.duration_since(last.time)
+                                            <= settings.double_click_interval
+                                        && last.position.distance(position)
+                                            <= settings.double_click_distance
+                                });
+                            if is_double_click {
+                                // Start over, so that a third click isn't a second double click.
+                                tao_state.last_click = None;
+                                input_events.double_click.send(DoubleClick {
+                                    button,
+                                    position,
+                                    window: window_entity,
+                                });
+                            } else {
+                                tao_state.last_click = Some(LastClick {
+                                    window: window_entity,
+                                    button,
+                                    position,
+                                    time: now,
+                                });
+                            }
+                        }
+
+                        let mouse_button_input = MouseButtonInput {
+                            button,
+                            state,
                             window: window_entity,
-                        });
+                        };
+                        send_timestamped(
+                            &mut timestamped_events.mouse_button_input,
+                            received,
+                            &mouse_button_input,
+                        );
+                        input_events.mouse_button_input.send(mouse_button_input);
                     }
                     // WindowEvent::TouchpadMagnify { delta, .. } => {
                     //     input_events
@@ -387,31 +857,49 @@ pub fn tao_runner(mut app: App) {
                     //         .touchpad_rotate_input
                     //         .send(TouchpadRotate(delta));
                     // }
-                    WindowEvent::MouseWheel { delta, .. } => match delta {
-                        event::MouseScrollDelta::LineDelta(x, y) => {
-                            input_events.mouse_wheel_input.send(MouseWheel {
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let mouse_wheel = match delta {
+                            event::MouseScrollDelta::LineDelta(x, y) => MouseWheel {
                                 unit: MouseScrollUnit::Line,
                                 x,
                                 y,
                                 window: window_entity,
-                            });
-                        }
-                        event::MouseScrollDelta::PixelDelta(p) => {
-                            input_events.mouse_wheel_input.send(MouseWheel {
+                            },
+                            event::MouseScrollDelta::PixelDelta(p) => MouseWheel {
                                 unit: MouseScrollUnit::Pixel,
                                 x: p.x as f32,
                                 y: p.y as f32,
                                 window: window_entity,
-                            });
-                        }
-                        _ => unimplemented!("tao added a new variant to MouseScrollDelta"),
-                    },
+                            },
+                            _ => unimplemented!("tao added a new variant to MouseScrollDelta"),
+                        };
+                        send_timestamped(
+                            &mut timestamped_events.mouse_wheel_input,
+                            received,
+                            &mouse_wheel,
+                        );
+                        let pixels_per_unit = match mouse_wheel.unit {
+                            MouseScrollUnit::Line => scroll_line_height.0,
+                            MouseScrollUnit::Pixel => 1.0,
+                        };
+                        let pixel_mouse_wheel = PixelMouseWheel {
+                            x: mouse_wheel.x * pixels_per_unit,
+                            y: mouse_wheel.y * pixels_per_unit,
+                            window: window_entity,
+                        };
+                        input_events.mouse_wheel_input.send(mouse_wheel);
+                        input_events.pixel_mouse_wheel_input.send(pixel_mouse_wheel);
+                    }
                     WindowEvent::Touch(touch) => {
                         let location = touch.location.to_logical(window.resolution.scale_factor());
 
-                        input_events
-                            .touch_input
-                            .send(converters::convert_touch_input(touch, location));
+                        let touch_input = converters::convert_touch_input(touch, location);
+                        send_timestamped(
+                            &mut timestamped_events.touch_input,
+                            received,
+                            &touch_input,
+                        );
+                        input_events.touch_input.send(touch_input);
                     }
                     WindowEvent::ReceivedImeText(c) => {
                         input_events.character_input.send(ReceivedCharacter {
@@ -524,9 +1012,19 @@ pub fn tao_runner(mut app: App) {
                     //     }),
                     // },
                     WindowEvent::ThemeChanged(theme) => {
+                        let theme = convert_tao_theme(theme);
+                        window.window_theme = Some(theme);
+
+                        // Only windows following the OS theme are told when it changes.
+                        if system_theme.0 != theme {
+                            system_theme.0 = theme;
+                            window_events
+                                .system_theme_changed
+                                .send(SystemThemeChanged { theme });
+                        }
                         window_events.window_theme_changed.send(WindowThemeChanged {
                             window: window_entity,
-                            theme: convert_tao_theme(theme),
+                            theme,
                         });
                     }
                     WindowEvent::Destroyed => {
@@ -545,13 +1043,20 @@ pub fn tao_runner(mut app: App) {
                 event: DeviceEvent::MouseMotion { delta: (x, y), .. },
                 ..
             } => {
-                let mut system_state: SystemState<EventWriter<MouseMotion>> =
-                    SystemState::new(&mut app.world);
-                let mut mouse_motion = system_state.get_mut(&mut app.world);
+                let mut system_state: SystemState<(
+                    EventWriter<MouseMotion>,
+                    EventWriter<Timestamped<MouseMotion>>,
+                    Res<TaoSettings>,
+                )> = SystemState::new(&mut app.world);
+                let (mut mouse_motion, mut timestamped_mouse_motion, settings) =
+                    system_state.get_mut(&mut app.world);
+                let received = settings.timestamp_input.then(Instant::now);
 
-                mouse_motion.send(MouseMotion {
+                let motion = MouseMotion {
                     delta: Vec2::new(x as f32, y as f32),
-                });
+                };
+                send_timestamped(&mut timestamped_mouse_motion, received, &motion);
+                mouse_motion.send(motion);
             }
             event::Event::Suspended => {
                 tao_state.active = false;
@@ -560,18 +1065,62 @@ pub fn tao_runner(mut app: App) {
                 tao_state.active = true;
             }
             event::Event::MainEventsCleared => {
-                if finished_and_setup_done {
+                let (settings, window_focused_query) = focused_window_state.get(&app.world);
+                let update = if tao_state.active {
+                    let focused = window_focused_query.iter().any(|window| window.focused);
+                    match settings.update_mode(focused) {
+                        UpdateMode::Continuous | UpdateMode::Reactive { .. } => true,
+                        UpdateMode::ReactiveLowPower { .. } => {
+                            tao_state.low_power_event
+                                || tao_state.redraw_request_sent
+                                || tao_state.timeout_reached
+                        }
+                    }
+                } else {
+                    false
+                };
+
+                if update && finished_and_setup_done {
                     tao_state.last_update = Instant::now();
                     app.update();
+
+                    // Exit right away, instead of on the next event, which may only come when
+                    // the user interacts with the window again.
+                    if app_exit_requested(&app, &mut app_exit_event_reader) {
+                        exiting = true;
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
                 }
             }
+            // tao sends this after `MainEventsCleared`, so the app can only be updated on the
+            // next loop iteration, see `RedrawEventsCleared`.
+            Event::RedrawRequested(_) => {
+                tao_state.os_redraw_requested = true;
+            }
             Event::RedrawEventsCleared => {
-                *control_flow = ControlFlow::Poll;
+                {
+                    let (settings, window_focused_query) = focused_window_state.get(&app.world);
+                    let focused = window_focused_query.iter().any(|window| window.focused);
+                    *control_flow = match settings.update_mode(focused) {
+                        UpdateMode::Continuous => ControlFlow::Poll,
+                        UpdateMode::Reactive { wait } | UpdateMode::ReactiveLowPower { wait } => {
+                            if let Some(next) = tao_state.last_update.checked_add(*wait) {
+                                ControlFlow::WaitUntil(next)
+                            } else {
+                                ControlFlow::Wait
+                            }
+                        }
+                    };
+                }
 
                 // This block needs to run after `app.update()` in `MainEventsCleared`. Otherwise,
                 // we won't be able to see redraw requests until the next event, defeating the
                 // purpose of a redraw request!
-                let mut redraw = false;
+                let mut redraw = std::mem::take(&mut tao_state.os_redraw_requested);
+                if redraw {
+                    *control_flow = ControlFlow::Poll;
+                }
                 if let Some(app_redraw_events) = app.world.get_resource::<Events<RequestRedraw>>() {
                     if redraw_event_reader.iter(app_redraw_events).last().is_some() {
                         *control_flow = ControlFlow::Poll;
@@ -586,7 +1135,7 @@ pub fn tao_runner(mut app: App) {
         }
 
         if tao_state.active {
-            let (commands, mut new_windows, created_window_writer, tao_windows) =
+            let (commands, mut new_windows, created_window_writer, tao_windows, system_theme) =
                 create_window_system_state.get_mut(&mut app.world);
 
             // Responsible for creating new windows
@@ -596,6 +1145,7 @@ pub fn tao_runner(mut app: App) {
                 new_windows.iter_mut(),
                 created_window_writer,
                 tao_windows,
+                system_theme,
             );
 
             create_window_system_state.apply(&mut app.world);
@@ -609,3 +1159,100 @@ pub fn tao_runner(mut app: App) {
         run(event_loop, event_handler);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aspect_ratio_follows_width() {
+        let size = lock_aspect_ratio(
+            PhysicalSize::new(1000, 450),
+            PhysicalSize::new(800, 400),
+            2.0,
+            &WindowResizeConstraints::default(),
+            1.0,
+        );
+        assert_eq!(size, PhysicalSize::new(1000, 500));
+    }
+
+    #[test]
+    fn aspect_ratio_follows_height() {
+        let size = lock_aspect_ratio(
+            PhysicalSize::new(820, 500),
+            PhysicalSize::new(800, 400),
+            2.0,
+            &WindowResizeConstraints::default(),
+            1.0,
+        );
+        assert_eq!(size, PhysicalSize::new(1000, 500));
+    }
+
+    #[test]
+    fn aspect_ratio_respects_min_size() {
+        // The minimum width comes from the minimum height here.
+        let constraints = WindowResizeConstraints {
+            min_width: 100.0,
+            min_height: 200.0,
+            ..Default::default()
+        };
+        let size = lock_aspect_ratio(
+            PhysicalSize::new(300, 100),
+            PhysicalSize::new(800, 400),
+            2.0,
+            &constraints,
+            1.0,
+        );
+        assert_eq!(size, PhysicalSize::new(400, 200));
+    }
+
+    #[test]
+    fn aspect_ratio_respects_max_size() {
+        // The maximum width comes from the maximum height here.
+        let constraints = WindowResizeConstraints {
+            max_width: 1000.0,
+            max_height: 400.0,
+            ..Default::default()
+        };
+        let size = lock_aspect_ratio(
+            PhysicalSize::new(1200, 600),
+            PhysicalSize::new(800, 400),
+            2.0,
+            &constraints,
+            1.0,
+        );
+        assert_eq!(size, PhysicalSize::new(800, 400));
+    }
+
+    #[test]
+    fn aspect_ratio_constraints_are_logical() {
+        let constraints = WindowResizeConstraints {
+            min_width: 300.0,
+            ..Default::default()
+        };
+        let size = lock_aspect_ratio(
+            PhysicalSize::new(400, 200),
+            PhysicalSize::new(800, 400),
+            2.0,
+            &constraints,
+            2.0,
+        );
+        assert_eq!(size, PhysicalSize::new(600, 300));
+    }
+
+    #[test]
+    fn invalid_aspect_ratio_keeps_size() {
+        let size = PhysicalSize::new(1000, 450);
+        let constraints = WindowResizeConstraints::default();
+        for aspect_ratio in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let locked = lock_aspect_ratio(
+                size,
+                PhysicalSize::new(800, 400),
+                aspect_ratio,
+                &constraints,
+                1.0,
+            );
+            assert_eq!(locked, size);
+        }
+    }
+}
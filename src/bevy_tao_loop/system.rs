@@ -1,17 +1,22 @@
+use bevy::asset::{AssetEvent, Assets};
 use bevy::ecs::{
     entity::Entity,
-    event::EventWriter,
-    prelude::{Changed, Component, Resource},
+    event::{EventReader, EventWriter},
+    prelude::{Changed, Component, DetectChanges, DetectChangesMut, Resource, With},
     removal_detection::RemovedComponents,
-    system::{Commands, NonSendMut, Query},
+    system::{Commands, Local, NonSend, NonSendMut, Query, Res, ResMut},
     world::Mut,
 };
 use bevy::log;
+use bevy::render::texture::Image;
 use bevy::utils::{
     tracing::{error, info, warn},
-    HashMap,
+    HashMap, HashSet,
+};
+use bevy::window::{
+    CursorGrabMode, PrimaryWindow, RawHandleWrapper, Window, WindowClosed, WindowCreated,
+    WindowFocused,
 };
-use bevy::window::{RawHandleWrapper, Window, WindowClosed, WindowCreated};
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 
 use tao::{
@@ -24,7 +29,14 @@ use super::{
     converters::{self, convert_tao_theme, set_window_level},
     get_best_videomode,
     get_fitting_videomode,
+    DragWindow,
+    RecreateWindow,
+    RelativeMouseMode,
+    SystemTheme,
     TaoWindows,
+    ToggleMaximizeWindow,
+    WindowIcon,
+    WindowRecreated,
 };
 
 /// System responsible for creating new windows whenever a [`Window`] component is added
@@ -37,6 +49,7 @@ pub(crate) fn create_window<'a>(
     created_windows: impl Iterator<Item = (Entity, Mut<'a, Window>)>,
     mut event_writer: EventWriter<WindowCreated>,
     mut tao_windows: NonSendMut<TaoWindows>,
+    mut system_theme: ResMut<SystemTheme>,
 ) {
     for (entity, mut window) in created_windows {
         if tao_windows.get_window(entity).is_some() {
@@ -51,17 +64,24 @@ pub(crate) fn create_window<'a>(
 
         let tao_window = tao_windows.create_window(event_loop, entity, &window);
 
-        window.window_theme = Some(convert_tao_theme(tao_window.theme()));
+        let theme = convert_tao_theme(tao_window.theme());
+        // Windows follow the OS theme unless they force one.
+        if window.window_theme.is_none() {
+            system_theme.set_if_neq(SystemTheme(theme));
+        }
+        window.window_theme = Some(theme);
 
         window
             .resolution
             .set_scale_factor(tao_window.scale_factor());
+        let raw_handle = RawHandleWrapper {
+            window_handle: tao_window.raw_window_handle(),
+            display_handle: tao_window.raw_display_handle(),
+        };
+        log::debug!(target: "bevy_tao", "Window {entity:?} raw handles: {raw_handle:?}");
         commands
             .entity(entity)
-            .insert(dbg!(RawHandleWrapper {
-                window_handle: tao_window.raw_window_handle(),
-                display_handle: tao_window.raw_display_handle(),
-            }))
+            .insert(raw_handle)
             .insert(CachedWindow {
                 window: window.clone(),
             });
@@ -91,6 +111,42 @@ pub(crate) fn despawn_window(
     }
 }
 
+/// Replace windows with a [`RecreateWindow`] event by a new window entity.
+///
+/// This runs in `PostUpdate`, so that [`despawn_window`] closes the old tao window in the same
+/// frame the new one is created.
+pub(crate) fn recreate_window(
+    mut commands: Commands,
+    mut recreate_events: EventReader<RecreateWindow>,
+    mut recreated_events: EventWriter<WindowRecreated>,
+    windows: Query<(&Window, Option<&PrimaryWindow>)>,
+    mut recreated: Local<HashSet<Entity>>,
+) {
+    recreated.clear();
+    for &RecreateWindow { window: old_window } in recreate_events.iter() {
+        if !recreated.insert(old_window) {
+            continue;
+        }
+        let Ok((window, primary)) = windows.get(old_window) else {
+            warn!(target: "bevy_tao", "Cannot recreate {old_window:?}, it is not a window");
+            continue;
+        };
+        info!(target: "bevy_tao", "Recreating window {:?} ({:?})", window.title, old_window);
+
+        let mut new_window = commands.spawn(window.clone());
+        if primary.is_some() {
+            new_window.insert(PrimaryWindow);
+        }
+        let new_window = new_window.id();
+        commands.entity(old_window).despawn();
+
+        recreated_events.send(WindowRecreated {
+            old_window,
+            new_window,
+        });
+    }
+}
+
 /// The cached state of the window so we can check which properties were changed from within the app.
 #[derive(Debug, Clone, Component)]
 pub struct CachedWindow {
@@ -176,7 +232,7 @@ pub(crate) fn changed_window(
             }
 
             if window.cursor.hit_test != cache.window.cursor.hit_test {
-                if let Err(err) = tao_window.set_ignore_cursor_events(window.cursor.hit_test) {
+                if let Err(err) = tao_window.set_ignore_cursor_events(!window.cursor.hit_test) {
                     window.cursor.hit_test = cache.window.cursor.hit_test;
                     warn!(
                         "Could not set cursor hit test for window {:?}: {:?}",
@@ -211,6 +267,9 @@ pub(crate) fn changed_window(
                 tao_window.set_min_inner_size(Some(min_inner_size));
                 if constraints.max_width.is_finite() && constraints.max_height.is_finite() {
                     tao_window.set_max_inner_size(Some(max_inner_size));
+                } else {
+                    // Lift a previous maximum size.
+                    tao_window.set_max_inner_size(None::<LogicalSize<f32>>);
                 }
             }
 
@@ -276,3 +335,132 @@ pub(crate) fn changed_window(
         }
     }
 }
+
+/// Set the icon of tao windows to the [`WindowIcon`] once its image is loaded.
+///
+/// This is a no-op until the [`WindowIcon`] resource is inserted.
+pub(crate) fn update_window_icon(
+    window_icon: Option<Res<WindowIcon>>,
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    windows: Query<Entity, With<CachedWindow>>,
+    tao_windows: NonSend<TaoWindows>,
+    mut has_icon: Local<HashSet<Entity>>,
+) {
+    let Some(window_icon) = window_icon else {
+        return;
+    };
+    let icon_modified = image_events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            handle == &window_icon.0
+        }
+        AssetEvent::Removed { .. } => false,
+    });
+    if window_icon.is_changed() || icon_modified {
+        has_icon.clear();
+    }
+    has_icon.retain(|entity| windows.contains(*entity));
+
+    if windows.iter().all(|entity| has_icon.contains(&entity)) {
+        return;
+    }
+    let Some(image) = images.get(&window_icon.0) else {
+        return;
+    };
+    let icon = match image.clone().try_into_dynamic() {
+        Ok(dynamic_image) => {
+            let rgba = dynamic_image.to_rgba8();
+            let (width, height) = rgba.dimensions();
+            tao::window::Icon::from_rgba(rgba.into_raw(), width, height)
+                .map_err(|err| err.to_string())
+        }
+        Err(err) => Err(err.to_string()),
+    };
+    let icon = match icon {
+        Ok(icon) => icon,
+        Err(err) => {
+            warn!(target: "bevy_tao", "Could not convert window icon image: {err}");
+            // Don't retry every frame, wait for the image or resource to change instead.
+            has_icon.extend(windows.iter());
+            return;
+        }
+    };
+    for entity in &windows {
+        if has_icon.contains(&entity) {
+            continue;
+        }
+        if let Some(tao_window) = tao_windows.get_window(entity) {
+            tao_window.set_window_icon(Some(icon.clone()));
+            has_icon.insert(entity);
+        }
+    }
+}
+
+/// Hide and lock the cursor of windows while [`RelativeMouseMode`] is enabled.
+///
+/// Windows are only changed when the mode is toggled, or when they are spawned while it is
+/// enabled, so that their own cursor settings are kept otherwise.
+///
+/// This must run before [`changed_window`], which applies the cursor changes to tao windows.
+pub(crate) fn update_relative_mouse_mode(
+    relative_mouse_mode: Res<RelativeMouseMode>,
+    mut focused_events: EventReader<WindowFocused>,
+    mut windows: Query<(Entity, &mut Window)>,
+    tao_windows: NonSend<TaoWindows>,
+    mut applied: Local<bool>,
+) {
+    let RelativeMouseMode(enabled) = *relative_mouse_mode;
+    let grab_mode = if enabled {
+        CursorGrabMode::Locked
+    } else {
+        CursorGrabMode::None
+    };
+
+    // The mode starts disabled, which leaves windows as they were spawned.
+    let toggled = enabled != *applied;
+    *applied = enabled;
+
+    for (_, mut window) in &mut windows {
+        if toggled || (enabled && window.is_added()) {
+            window.cursor.grab_mode = grab_mode;
+            window.cursor.visible = !enabled;
+        }
+    }
+
+    if !enabled {
+        focused_events.clear();
+        return;
+    }
+    // The `Window` component didn't change when the platform released the cursor, so
+    // `changed_window` won't lock it again.
+    for event in focused_events.iter().filter(|event| event.focused) {
+        let Ok((entity, window)) = windows.get(event.window) else {
+            continue;
+        };
+        if let Some(tao_window) = tao_windows.get_window(entity) {
+            super::tao_windows::attempt_grab(tao_window, window.cursor.grab_mode);
+            tao_window.set_cursor_visible(window.cursor.visible);
+        }
+    }
+}
+
+/// Apply [`DragWindow`] and [`ToggleMaximizeWindow`] events to tao windows.
+pub(crate) fn control_window(
+    mut drag_events: EventReader<DragWindow>,
+    mut toggle_maximize_events: EventReader<ToggleMaximizeWindow>,
+    tao_windows: NonSend<TaoWindows>,
+) {
+    for event in drag_events.iter() {
+        let Some(tao_window) = tao_windows.get_window(event.window) else {
+            continue;
+        };
+        if let Err(err) = tao_window.drag_window() {
+            warn!(target: "bevy_tao", "Could not drag window {:?}: {err}", event.window);
+        }
+    }
+    for event in toggle_maximize_events.iter() {
+        if let Some(tao_window) = tao_windows.get_window(event.window) {
+            tao_window.set_maximized(!tao_window.is_maximized());
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use tao::clipboard::Clipboard;
+
+/// A resource to read and write the OS clipboard through [`tao`].
+///
+/// On Linux, the clipboard goes through GTK, which must only be used from the main thread.
+/// Like [`TaoWindows`](super::TaoWindows), this is therefore only accessible with bevy's
+/// non-send functions and in NonSend systems.
+///
+/// # Supported platforms
+///
+/// The clipboard is unsupported on Android and iOS, reading it always returns `None`.
+#[derive(Debug, Default)]
+pub struct TaoClipboard {
+    clipboard: Clipboard,
+    _not_send_sync: core::marker::PhantomData<*const ()>,
+}
+
+impl TaoClipboard {
+    /// Replace the content of the clipboard with `text`.
+    pub fn set_text(&mut self, text: impl AsRef<str>) {
+        self.clipboard.write_text(text);
+    }
+
+    /// Get the content of the clipboard, if it holds text.
+    pub fn get_text(&self) -> Option<String> {
+        self.clipboard.read_text()
+    }
+}
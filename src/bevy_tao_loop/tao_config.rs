@@ -1,4 +1,8 @@
+use bevy::asset::Handle;
 use bevy::ecs::system::Resource;
+use bevy::render::texture::Image;
+use bevy::utils::Duration;
+use bevy::window::WindowTheme;
 
 /// A resource for configuring usage of the [`winit`] library.
 #[derive(Debug, Resource)]
@@ -30,6 +34,65 @@ pub struct TaoSettings {
     pub focused_mode: UpdateMode,
     /// Configures how the winit event loop updates while the window is *not* focused.
     pub unfocused_mode: UpdateMode,
+    /// The longest delay between two presses of a mouse button for them to be
+    /// sent as a [`DoubleClick`](super::DoubleClick).
+    pub double_click_interval: Duration,
+    /// How far, in logical pixels, the cursor may move between two presses of a mouse
+    /// button for them to still be sent as a [`DoubleClick`](super::DoubleClick).
+    pub double_click_distance: f32,
+    /// Whether to send a [`Timestamped`](super::Timestamped) copy of each forwarded input event.
+    ///
+    /// Disabled by default, as it doubles the number of input events.
+    pub timestamp_input: bool,
+    /// Whether to forward device events, such as raw [`MouseMotion`] deltas, while no window
+    /// is focused.
+    ///
+    /// Enabled by default so that mouse motion reaches the app regardless of focus. Disabling
+    /// it can reduce CPU usage in the background.
+    ///
+    /// # Supported platforms
+    ///
+    /// This only has an effect on Windows, the only platform where tao filters device events.
+    /// On Linux, tao delivers device events under X11 but not under Wayland.
+    ///
+    /// [`MouseMotion`]: bevy::input::mouse::MouseMotion
+    pub unfocused_device_events: bool,
+    /// Which display server tao should connect to on Linux and BSDs.
+    ///
+    /// This is only read when [`TaoPlugin`](super::TaoPlugin) is built, so the `TaoSettings`
+    /// resource must be inserted before adding the plugin for it to have any effect.
+    ///
+    /// Anything but [`LinuxWindowBackend::Auto`] sets the `GDK_BACKEND` environment variable,
+    /// overriding the user's own. This isn't thread-safe, setting `GDK_BACKEND` before starting
+    /// the app is preferable when possible.
+    pub linux_window_backend: LinuxWindowBackend,
+}
+impl TaoSettings {
+    /// Configure tao with common settings for a game.
+    pub fn game() -> Self {
+        TaoSettings::default()
+    }
+
+    /// Configure tao with common settings for a desktop application.
+    pub fn desktop_app() -> Self {
+        TaoSettings {
+            focused_mode: UpdateMode::Reactive {
+                wait: Duration::from_secs(5),
+            },
+            unfocused_mode: UpdateMode::ReactiveLowPower {
+                wait: Duration::from_secs(60),
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Gets the configured [`UpdateMode`] depending on whether the window is focused or not
+    pub fn update_mode(&self, focused: bool) -> &UpdateMode {
+        match focused {
+            true => &self.focused_mode,
+            false => &self.unfocused_mode,
+        }
+    }
 }
 impl Default for TaoSettings {
     fn default() -> Self {
@@ -37,13 +100,131 @@ impl Default for TaoSettings {
             return_from_run: false,
             focused_mode: UpdateMode::Continuous,
             unfocused_mode: UpdateMode::Continuous,
+            double_click_interval: Duration::from_millis(500),
+            double_click_distance: 4.0,
+            timestamp_input: false,
+            unfocused_device_events: true,
+            linux_window_backend: LinuxWindowBackend::Auto,
         }
     }
 }
 
+/// The display server used by tao's GTK backend on Linux and BSDs.
+///
+/// This decides whether windows get X11 or Wayland raw handles, and therefore which
+/// kind of surface the renderer creates for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinuxWindowBackend {
+    /// Let GTK pick the display server, honoring the `GDK_BACKEND` environment variable.
+    #[default]
+    Auto,
+    /// Connect to an X server, through XWayland on a Wayland desktop.
+    X11,
+    /// Connect to a Wayland compositor.
+    Wayland,
+}
+
 /// Configure how the winit event loop should update.
 #[derive(Debug)]
 pub enum UpdateMode {
     /// The event loop will update continuously, running as fast as possible.
     Continuous,
+    /// The event loop will only update if there is a tao event, a redraw is requested, or the
+    /// maximum wait time has elapsed.
+    ///
+    /// ## Note
+    ///
+    /// Once the app has executed all bevy systems and reaches the end of the event loop, there is
+    /// no way to force the app to wake and update again, unless a `tao` event (such as user
+    /// input, or the window being resized) is received or the time limit is reached.
+    Reactive {
+        /// The maximum time to wait before the event loop runs again.
+        ///
+        /// Note that Bevy will wait indefinitely if the duration is too high (such as [`Duration::MAX`]).
+        wait: Duration,
+    },
+    /// The event loop will only update if there is a tao event from direct interaction with the
+    /// window (e.g. mouseover), a redraw is requested, or the maximum wait time has elapsed.
+    ///
+    /// ## Note
+    ///
+    /// Once the app has executed all bevy systems and reaches the end of the event loop, there is
+    /// no way to force the app to wake and update again, unless a `tao` event (such as user
+    /// input, or the window being resized) is received or the time limit is reached.
+    ///
+    /// Raw [`MouseMotion`](bevy::input::mouse::MouseMotion) doesn't wake the app up in this mode.
+    ReactiveLowPower {
+        /// The maximum time to wait before the event loop runs again.
+        ///
+        /// Note that Bevy will wait indefinitely if the duration is too high (such as [`Duration::MAX`]).
+        wait: Duration,
+    },
 }
+
+/// A resource to switch windows to relative mouse mode, as used for first person cameras.
+///
+/// While enabled, the cursor is hidden and locked to the windows, and only raw
+/// [`MouseMotion`] deltas are forwarded to bevy: [`CursorMoved`] events are not sent.
+/// Disabling it shows and releases the cursor again.
+///
+/// The cursor is locked again when a window regains focus, since most platforms release it
+/// when focus is lost.
+///
+/// # Supported platforms
+///
+/// tao can't lock the cursor on Linux, where it is only hidden.
+///
+/// [`MouseMotion`]: bevy::input::mouse::MouseMotion
+/// [`CursorMoved`]: bevy::window::CursorMoved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub struct RelativeMouseMode(pub bool);
+
+/// A resource for the height, in pixels, of a line of scrolling.
+///
+/// Mice usually scroll by lines while trackpads scroll by pixels. This is used to convert
+/// line deltas to pixels when sending [`PixelMouseWheel`](super::PixelMouseWheel) events.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct ScrollLineHeight(pub f32);
+impl Default for ScrollLineHeight {
+    fn default() -> Self {
+        ScrollLineHeight(20.0)
+    }
+}
+
+/// A resource to keep windows at a fixed aspect ratio, width divided by height, while they are
+/// resized.
+///
+/// When a window is resized, it is snapped to the closest size with this aspect ratio, following
+/// the dimension that changed the most and staying within the window's
+/// [`resize_constraints`](bevy::window::Window::resize_constraints). Windows are not snapped
+/// until they are first resized, and fullscreen or maximized windows are left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct AspectRatioLock(pub f32);
+
+/// A resource holding the dark or light theme of the OS.
+///
+/// This is read from the first window that doesn't force its
+/// [`window_theme`](bevy::window::Window::window_theme), and updated when such a window
+/// reports a theme change, along with a [`SystemThemeChanged`](super::SystemThemeChanged) event.
+///
+/// # Supported platforms
+///
+/// The theme can't be detected on Android and iOS, where this is always `Light`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct SystemTheme(pub WindowTheme);
+impl Default for SystemTheme {
+    fn default() -> Self {
+        SystemTheme(WindowTheme::Light)
+    }
+}
+
+/// A resource to set the icon of all windows from an [`Image`] asset.
+///
+/// The icon is applied once the image is loaded, and re-applied when the image changes
+/// or a new window is created.
+///
+/// # Supported platforms
+///
+/// Window icons are only supported on Windows and Linux. This does nothing on other platforms.
+#[derive(Debug, Clone, Resource)]
+pub struct WindowIcon(pub Handle<Image>);
@@ -0,0 +1,132 @@
+use bevy::ecs::{entity::Entity, event::Event};
+use bevy::input::mouse::MouseButton;
+use bevy::math::Vec2;
+use bevy::utils::Instant;
+use bevy::window::WindowTheme;
+
+/// An event sent when a mouse button is pressed twice in quick succession at the same place.
+///
+/// The delay and distance allowed between the two presses are configured with
+/// [`TaoSettings::double_click_interval`](super::TaoSettings::double_click_interval) and
+/// [`TaoSettings::double_click_distance`](super::TaoSettings::double_click_distance).
+///
+/// The second press is still sent as a regular [`MouseButtonInput`](bevy::input::mouse::MouseButtonInput).
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DoubleClick {
+    /// The button that was double-clicked.
+    pub button: MouseButton,
+    /// The logical position of the cursor on the second press.
+    pub position: Vec2,
+    /// Window that received the clicks.
+    pub window: Entity,
+}
+
+/// A mouse wheel event, with the scroll delta always in pixels.
+///
+/// This is sent after each [`MouseWheel`](bevy::input::mouse::MouseWheel), which keeps the
+/// unit reported by the device. Line deltas are converted using the [`ScrollLineHeight`]
+/// resource, so that mice and trackpads scroll at a consistent speed.
+///
+/// [`ScrollLineHeight`]: super::ScrollLineHeight
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PixelMouseWheel {
+    /// The horizontal scroll delta, in pixels.
+    pub x: f32,
+    /// The vertical scroll delta, in pixels.
+    pub y: f32,
+    /// Window that received the input.
+    pub window: Entity,
+}
+
+/// An event to replace a window with a freshly created tao window.
+///
+/// This is useful when the current window can't be kept, for example after switching display.
+/// The renderer can't create a new surface for an existing window entity, so the window entity
+/// is despawned, and a new one is spawned with the same [`Window`] and
+/// [`PrimaryWindow`] components. Other components of the window entity are not kept.
+/// A [`WindowRecreated`] event is sent with the new entity.
+///
+/// [`Window`]: bevy::window::Window
+/// [`PrimaryWindow`]: bevy::window::PrimaryWindow
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecreateWindow {
+    /// Window to recreate.
+    pub window: Entity,
+}
+
+/// An event sent when a window was replaced following a [`RecreateWindow`].
+///
+/// Cameras rendering to the window with [`WindowRef::Entity`](bevy::window::WindowRef::Entity)
+/// should be updated to target the new window.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowRecreated {
+    /// The despawned window entity.
+    pub old_window: Entity,
+    /// The entity of the new window.
+    pub new_window: Entity,
+}
+
+/// An event to start moving a window with the mouse, as if its title bar was dragged.
+///
+/// This allows undecorated windows, see [`Window::decorations`], to draw their own title bar.
+/// Send it when the left mouse button is pressed on it, the move stops when the button is
+/// released. Window controls can be implemented with [`ToggleMaximizeWindow`],
+/// [`Window::set_minimized`] and by sending a [`WindowCloseRequested`].
+///
+/// # Supported platforms
+///
+/// This is unsupported on Android and iOS. On macOS, the button release that ends the move may
+/// not be sent to bevy.
+///
+/// [`Window::decorations`]: bevy::window::Window::decorations
+/// [`Window::set_minimized`]: bevy::window::Window::set_minimized
+/// [`WindowCloseRequested`]: bevy::window::WindowCloseRequested
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DragWindow {
+    /// Window to move.
+    pub window: Entity,
+}
+
+/// An event to maximize a window, or restore it if it is already maximized.
+///
+/// Custom title bars usually do this on a [`DoubleClick`].
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToggleMaximizeWindow {
+    /// Window to maximize or restore.
+    pub window: Entity,
+}
+
+/// An event sent when the OS switches between dark and light themes.
+///
+/// The new theme is also stored in the [`SystemTheme`](super::SystemTheme) resource.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemThemeChanged {
+    /// The new theme.
+    pub theme: WindowTheme,
+}
+
+/// A copy of an input event forwarded from tao, along with when it was received.
+///
+/// This is only sent when [`TaoSettings::timestamp_input`](super::TaoSettings::timestamp_input)
+/// is enabled, for [`KeyboardInput`], [`MouseButtonInput`], [`MouseWheel`], [`MouseMotion`],
+/// [`CursorMoved`] and [`TouchInput`]. Each `Timestamped<T>` is sent in the same frame, and in
+/// the same order, as the `T` it copies.
+///
+/// tao doesn't report when the OS generated an event, so `received` is the time the tao event
+/// loop handed it to Bevy. It can be used to measure input latency or to time gestures.
+///
+/// [`KeyboardInput`]: bevy::input::keyboard::KeyboardInput
+/// [`MouseButtonInput`]: bevy::input::mouse::MouseButtonInput
+/// [`MouseWheel`]: bevy::input::mouse::MouseWheel
+/// [`MouseMotion`]: bevy::input::mouse::MouseMotion
+/// [`CursorMoved`]: bevy::window::CursorMoved
+/// [`TouchInput`]: bevy::input::touch::TouchInput
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timestamped<T> {
+    /// The forwarded input event.
+    pub event: T,
+    /// When the tao event loop received the event.
+    pub received: Instant,
+}
+
+impl<T: Event> Event for Timestamped<T> {}
@@ -2,7 +2,10 @@
 
 use bevy::ecs::entity::Entity;
 
-use bevy::utils::{tracing::warn, HashMap};
+use bevy::utils::{
+    tracing::{debug, warn},
+    HashMap,
+};
 use bevy::window::{CursorGrabMode, Window, WindowMode, WindowPosition, WindowResolution};
 
 use tao::{
@@ -77,7 +80,8 @@ impl TaoWindows {
         tao_window_builder = tao_window_builder
             .with_theme(window.window_theme.map(convert_window_theme))
             .with_resizable(window.resizable)
-            .with_decorations(window.decorations);
+            .with_decorations(window.decorations)
+            .with_transparent(window.transparent);
 
         let constraints = window.resize_constraints.check_constraints();
         let min_inner_size = LogicalSize {
@@ -99,7 +103,8 @@ impl TaoWindows {
             };
 
         let tao_window_builder = tao_window_builder.with_title(window.title.as_str());
-        let tao_window = dbg!(tao_window_builder).build(event_loop).unwrap();
+        debug!(target: "bevy_tao", "Building tao window {:?}", tao_window_builder);
+        let tao_window = tao_window_builder.build(event_loop).unwrap();
 
         // Do not set the grab mode on window creation if it's none, this can fail on mobile
         if window.cursor.grab_mode != CursorGrabMode::None {
@@ -111,7 +116,7 @@ impl TaoWindows {
         // Do not set the cursor hittest on window creation if it's false, as it will always fail on some
         // platforms and log an unfixable warning.
         if !window.cursor.hit_test {
-            if let Err(err) = tao_window.set_ignore_cursor_events(window.cursor.hit_test) {
+            if let Err(err) = tao_window.set_ignore_cursor_events(!window.cursor.hit_test) {
                 warn!(
                     "Could not set cursor hit test for window {:?}: {:?}",
                     window.title, err
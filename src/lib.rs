@@ -0,0 +1 @@
+pub mod bevy_tao_loop;
@@ -1,4 +1,4 @@
-mod bevy_tao_loop;
+use wrytaobevy::bevy_tao_loop;
 
 use bevy::prelude::*;
 